@@ -8,10 +8,17 @@ export interface SerializableConversion {
     context: Record<string, unknown>;
 }
 
+export interface PhysicalRange {
+    minimum: number;
+    maximum: number;
+}
+
 export interface SerializableConversionData {
     conversion: SerializableConversion | null;
     textValues: TextValue[];
     unit: string | null;
+    /** Declared range of converted values, if the file marks it as valid. */
+    physicalRange?: PhysicalRange | undefined;
 }
 
 export function deserializeConversion(data: SerializableConversionData): undefined | ((value: number) => number | string) {
@@ -0,0 +1,159 @@
+import { describe, it, expect } from 'vitest';
+import { ChannelType, DataGroupLoader, DataType, type AbstractChannel, type AbstractDataGroup, type LoadOptions } from './decoder';
+
+function createChannel(dataType: DataType, byteOffset: number, bitOffset: number, bitCount: number): AbstractChannel {
+    return { name: ['test'], type: ChannelType.Signal, dataType, byteOffset, bitOffset, bitCount };
+}
+
+// Splits the data into blocks of at most blockSize bytes, so records can straddle block boundaries
+function createLoader(dataGroup: AbstractDataGroup, data: number[], blockSize: number = Infinity): DataGroupLoader {
+    const bytes = new Uint8Array(data);
+    return new DataGroupLoader(dataGroup, async () => (async function* () {
+        for (let offset = 0; offset < bytes.length; offset += blockSize) {
+            yield new DataView(bytes.buffer, offset, Math.min(blockSize, bytes.length - offset));
+        }
+    })());
+}
+
+async function load(loader: DataGroupLoader, channels: AbstractChannel[], options?: LoadOptions): Promise<(number | bigint)[][]> {
+    const values = channels.map(() => [] as (number | bigint)[]);
+    const sequences = new Map<AbstractChannel, { push(value: number | bigint): void }>();
+    channels.forEach((channel, i) => sequences.set(channel, { push: value => { values[i].push(value); } }));
+    await loader.loadInto(sequences, options);
+    return values;
+}
+
+async function readChannel(channel: AbstractChannel, records: number[][]): Promise<(number | bigint)[]> {
+    const loader = createLoader(
+        { recordIdSize: 0, groups: [{ recordId: 0, dataBytes: records[0].length, invalidationBytes: 0, channels: [channel] }] },
+        records.flat(),
+    );
+    const [values] = await load(loader, [channel]);
+    return values;
+}
+
+// Places the raw bits of value at bitOffset within a little endian record of the given length
+function packBits(value: bigint, bitOffset: number, length: number): number[] {
+    const shifted = value << BigInt(bitOffset);
+    return Array.from({ length }, (_, i) => Number((shifted >> BigInt(i * 8)) & 0xFFn));
+}
+
+describe('DataGroupLoader', () => {
+    it('should read unsigned integers that start part way through a byte', async () => {
+        // 12-bit value 0xABC at bit 4, with the surrounding bits set to make sure they are masked out
+        const channel = createChannel(DataType.UintLe, 0, 4, 12);
+        const values = await readChannel(channel, [[0xCF, 0xAB, 0xFF], [0x0F, 0x00, 0xFF]]);
+        expect(values).toEqual([0xABC, 0]);
+    });
+
+    it('should sign extend signed integers that start part way through a byte', async () => {
+        const channel = createChannel(DataType.IntLe, 0, 4, 12);
+        const values = await readChannel(channel, [[0xF0, 0xFF], [0x10, 0x00], [0x00, 0x80]]);
+        expect(values).toEqual([-1, 1, -2048]);
+    });
+
+    it('should read big endian integers that start part way through a byte', async () => {
+        const channel = createChannel(DataType.UintBe, 0, 4, 12);
+        const values = await readChannel(channel, [[0xAB, 0xCF]]);
+        expect(values).toEqual([0xABC]);
+    });
+
+    it('should read 16-bit floats', async () => {
+        const channel = createChannel(DataType.FloatLe, 0, 0, 16);
+        const bits = [0x3C00, 0xC000, 0x3555, 0x7BFF, 0x0001, 0x8000, 0x7C00, 0x7E00];
+        const values = await readChannel(channel, bits.map(b => [b & 0xFF, b >> 8]));
+        expect(values).toEqual([1, -2, 0.333251953125, 65504, 2 ** -24, -0, Infinity, NaN]);
+    });
+
+    it('should read big endian 16-bit floats that start part way through a byte', async () => {
+        const channel = createChannel(DataType.FloatBe, 0, 4, 16);
+        // 0xC500 (-5) shifted up by 4 bits, with the unused bits either side set
+        const values = await readChannel(channel, [[0xFC, 0x50, 0x0F]]);
+        expect(values).toEqual([-5]);
+    });
+
+    it('should read 32-bit floats that start part way through a byte', async () => {
+        const bits = new DataView(new ArrayBuffer(4));
+        bits.setFloat32(0, -1.5, true);
+        const channel = createChannel(DataType.FloatLe, 0, 3, 32);
+        const record = packBits(BigInt(bits.getUint32(0, true)), 3, 5);
+        // Set the unused bits either side of the float to make sure they are masked out
+        record[0] |= 0x07;
+        record[4] |= 0xF8;
+        const values = await readChannel(channel, [record]);
+        expect(values).toEqual([-1.5]);
+    });
+
+    it('should read 64-bit floats that start part way through a byte', async () => {
+        const bits = new DataView(new ArrayBuffer(8));
+        bits.setFloat64(0, 1234.5678, true);
+        const channel = createChannel(DataType.FloatLe, 0, 5, 64);
+        const values = await readChannel(channel, [packBits(bits.getBigUint64(0, true), 5, 9)]);
+        expect(values).toEqual([1234.5678]);
+    });
+
+    it.each([1, 2, 4, 8])('should route records by %i-byte record IDs', async (recordIdSize) => {
+        const first = createChannel(DataType.UintLe, 0, 0, 8);
+        const second = createChannel(DataType.UintLe, 0, 0, 16);
+        const recordId = (id: number) => packBits(BigInt(id), 0, recordIdSize);
+        const data = [
+            ...recordId(1), 0x11,
+            ...recordId(2), 0x22, 0x01,
+            ...recordId(1), 0x33,
+            ...recordId(2), 0x44, 0x02,
+        ];
+        const dataGroup: AbstractDataGroup = {
+            recordIdSize,
+            groups: [
+                { recordId: 1, dataBytes: 1, invalidationBytes: 0, channels: [first] },
+                { recordId: 2, dataBytes: 2, invalidationBytes: 0, channels: [second] },
+            ],
+        };
+
+        expect(await load(createLoader(dataGroup, data), [first, second])).toEqual([[0x11, 0x33], [0x122, 0x244]]);
+        // Small blocks split both record IDs and record data across block boundaries
+        expect(await load(createLoader(dataGroup, data, 3), [first, second])).toEqual([[0x11, 0x33], [0x122, 0x244]]);
+    });
+
+    it('should reject records with an unknown record ID', async () => {
+        const channel = createChannel(DataType.UintLe, 0, 0, 8);
+        const loader = createLoader(
+            { recordIdSize: 2, groups: [{ recordId: 1, dataBytes: 1, invalidationBytes: 0, channels: [channel] }] },
+            [0x01, 0x00, 0x11, 0x02, 0x00, 0x22],
+        );
+        await expect(load(loader, [channel])).rejects.toThrow('Unknown record ID: 2');
+    });
+
+    it('should report a truncated record at the end of the data', async () => {
+        const channel = createChannel(DataType.UintLe, 0, 0, 8);
+        const dataGroup = { recordIdSize: 0, groups: [{ recordId: 0, dataBytes: 2, invalidationBytes: 0, channels: [channel] }] };
+        const truncated: number[] = [];
+
+        const [values] = await load(createLoader(dataGroup, [1, 0, 2, 0, 3], 3), [channel], { onTruncatedRecord: count => truncated.push(count) });
+        expect(values).toEqual([1, 2]);
+        expect(truncated).toEqual([1]);
+    });
+
+    it('should not report a truncated record when the data ends on a record boundary', async () => {
+        const channel = createChannel(DataType.UintLe, 0, 0, 8);
+        const dataGroup = { recordIdSize: 0, groups: [{ recordId: 0, dataBytes: 2, invalidationBytes: 0, channels: [channel] }] };
+        const truncated: number[] = [];
+
+        await load(createLoader(dataGroup, [1, 0, 2, 0], 3), [channel], { onTruncatedRecord: count => truncated.push(count) });
+        expect(truncated).toEqual([]);
+    });
+
+    it('should decode CANopen dates to unix seconds', async () => {
+        const channel = createChannel(DataType.CanOpenDate, 0, 0, 56);
+        // 2024-03-15 12:34:56.789, with the summer time and day of week bits set
+        const values = await readChannel(channel, [[0xD5, 0xDD, 34, 12 | 0x80, 15 | (5 << 5), 3, 24]]);
+        expect(values[0]).toBeCloseTo(Date.UTC(2024, 2, 15, 12, 34, 56, 789) / 1000, 6);
+    });
+
+    it('should decode CANopen times to unix seconds', async () => {
+        const channel = createChannel(DataType.CanOpenTime, 0, 0, 48);
+        // 1.5 s after midnight, 2 days after 1984-01-01, with the reserved bits set
+        const values = await readChannel(channel, [[0xDC, 0x05, 0x00, 0xF0, 0x02, 0x00]]);
+        expect(values[0]).toBeCloseTo(Date.UTC(1984, 0, 3, 0, 0, 1, 500) / 1000, 6);
+    });
+});
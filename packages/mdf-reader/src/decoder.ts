@@ -1,6 +1,9 @@
 // Maximum number of integer bits that can be represented exactly in a js number
 const maxSafeBits: number = 53;
 
+// CANopen TIME_OF_DAY values count days from 1984-01-01, expressed here in unix seconds
+const canOpenEpoch: number = Date.UTC(1984, 0, 1) / 1000;
+
 export enum NumberType {
     Float64 = 0,
     BigInt64 = 1,
@@ -34,6 +37,8 @@ export enum DataType {
     FloatLe = 4,
     FloatBe = 5,
     Unknown = 6,
+    CanOpenDate = 7,
+    CanOpenTime = 8,
 }
 
 export interface AbstractDataGroup {
@@ -61,6 +66,8 @@ export interface AbstractChannel {
 export interface LoadOptions {
     onProgress?: (rowCount: number) => void;
     progressInterval?: number;
+    /** Called with the number of trailing bytes discarded when the data ends part way through a record. */
+    onTruncatedRecord?: (byteCount: number) => void;
 }
 
 export class DataGroupLoader {
@@ -97,7 +104,7 @@ export class DataGroupLoader {
         const totalRows = this.data.totalRows ?? 0;
         const progressInterval = options?.progressInterval ?? 10000;
         let nextProgress = options?.onProgress ? progressInterval : Infinity;
-        await parseData(
+        const truncatedBytes = await parseData(
             this.data.recordIdSize,
             await this.blocks(),
             records,
@@ -114,6 +121,13 @@ export class DataGroupLoader {
                 return rowCount == totalRows;
             });
         console.log(`  Total Rows: ${rowCount}`);
+        if (truncatedBytes > 0) {
+            if (options?.onTruncatedRecord) {
+                options.onTruncatedRecord(truncatedBytes);
+            } else {
+                console.warn(`Data ended part way through a record, discarding ${truncatedBytes} trailing bytes`);
+            }
+        }
     }
 }
 
@@ -124,9 +138,22 @@ function getLoader(dataType: DataType, byteOffset: number, bitOffset: number, bi
             case DataType.FloatBe: {
                 const littleEndian = dataType === DataType.FloatLe;
                 if (bitOffset != 0) {
-                    throw new Error(`Unsupported bit offset ${bitOffset} for FloatLe`);
+                    // Reassemble the raw bits of an unaligned float and reinterpret them through the scratch view
+                    if (bitCount === 16) {
+                        return `return halfToFloat(${getBitFieldExpression(littleEndian, byteOffset, bitOffset, bitCount)});`;
+                    } else if (bitCount === 32) {
+                        return `scratch.setUint32(0, ${getBitFieldExpression(littleEndian, byteOffset, bitOffset, bitCount)}, true);` +
+                            `return scratch.getFloat32(0, true);`;
+                    } else if (bitCount === 64) {
+                        return `scratch.setBigUint64(0, ${getBitFieldExpression(littleEndian, byteOffset, bitOffset, bitCount)}, true);` +
+                            `return scratch.getFloat64(0, true);`;
+                    } else {
+                        throw new Error(`Unsupported bit count ${bitCount} for FloatLe`);
+                    }
                 }
-                if (bitCount === 32) {
+                if (bitCount === 16) {
+                    return `return halfToFloat(view.getUint16(${byteOffset}, ${littleEndian}));`;
+                } else if (bitCount === 32) {
                     return `return view.getFloat32(${byteOffset}, ${littleEndian});`;
                 } else if (bitCount === 64) {
                     return `return view.getFloat64(${byteOffset}, ${littleEndian});`;
@@ -157,43 +184,78 @@ function getLoader(dataType: DataType, byteOffset: number, bitOffset: number, bi
                     return "return 0;";
                 }
                 // Complex case - with masking and/or shifting
-                const useBigInt = bitCount > maxSafeBits;
-                const numberConversion = (v: string) => useBigInt ? `BigInt(${v})` : v;
-                const primitive = (v: bigint | number) => useBigInt ? `${v}n` : `${v}`;
-                
-                const parts = [];
-                const end = Math.ceil((bitCount + bitOffset) / 8);
-                for (let i = 0; i < end; i++)
-                {
-                    const byte = littleEndian ? byteOffset + i : byteOffset + end - 1 - i;
-                    const shift = i * 8 - (bitOffset % 8);
-                    if (shift == 0) {
-                        parts.push(numberConversion(`view.getUint8(${byte})`));
-                    } else if (shift < 0) {
-                        parts.push(`(${numberConversion(`view.getUint8(${byte})`)} >> ${primitive(-shift)})`);
-                    } else {
-                        parts.push(`(${numberConversion(`view.getUint8(${byte})`)} << ${primitive(shift)})`);
-                    }
-                }
-                
-                const mask = (1n << BigInt(bitCount)) - 1n;
+                const value = getBitFieldExpression(littleEndian, byteOffset, bitOffset, bitCount);
                 if (isSigned) {
+                    const primitive = (v: bigint) => bitCount > maxSafeBits ? `${v}n` : `${v}`;
                     const signBit = 1n << (BigInt(bitCount) - 1n);
                     const signAdjust = 1n << BigInt(bitCount);
-                    return `const value = (${parts.join(" | ")}) & ${primitive(mask)};` +
+                    return `const value = ${value};` +
                         `return value >= ${primitive(signBit)} ? value - ${primitive(signAdjust)} : value;`;
                 } else {
-                    return `return (${parts.join(" | ")}) & ${primitive(mask)};`;
+                    return `return ${value};`;
                 }
             }
+            case DataType.CanOpenDate:
+                // 7 bytes: milliseconds within the minute, minute, hour, day, month and years since 2000, decoded to
+                // unix seconds
+                return `return Date.UTC(` +
+                    `2000 + (view.getUint8(${byteOffset + 6}) & 0x7F), ` +
+                    `(view.getUint8(${byteOffset + 5}) & 0x3F) - 1, ` +
+                    `view.getUint8(${byteOffset + 4}) & 0x1F, ` +
+                    `view.getUint8(${byteOffset + 3}) & 0x1F, ` +
+                    `view.getUint8(${byteOffset + 2}) & 0x3F) / 1000 + ` +
+                    `view.getUint16(${byteOffset}, true) / 1000;`;
+            case DataType.CanOpenTime:
+                // 6 bytes: 28-bit milliseconds since midnight followed by days since 1984-01-01, decoded to unix seconds
+                return `return ${canOpenEpoch} + view.getUint16(${byteOffset + 4}, true) * 86400 + ` +
+                    `(view.getUint32(${byteOffset}, true) & 0x0FFFFFFF) / 1000;`;
             default:
                 return "return 0;";
         }
     }
-    return new Function("view", getExpression()) as (view: DataView) => number;
+    const scratch = new DataView(new ArrayBuffer(8));
+    return new Function("scratch", "halfToFloat", `return function(view) { ${getExpression()} };`)(scratch, halfToFloat) as (view: DataView) => number;
+}
+
+// Converts the raw bits of an IEEE 754 half precision float, since DataView has no getFloat16 in all browsers yet
+function halfToFloat(bits: number): number {
+    const sign = (bits & 0x8000) !== 0 ? -1 : 1;
+    const exponent = (bits >> 10) & 0x1F;
+    const fraction = bits & 0x3FF;
+    if (exponent === 0) {
+        return sign * fraction * 2 ** -24;
+    } else if (exponent === 0x1F) {
+        return fraction === 0 ? sign * Infinity : NaN;
+    }
+    return sign * (1024 + fraction) * 2 ** (exponent - 25);
+}
+
+// Builds an expression for the unsigned bitCount-bit field that starts bitOffset bits into the value at byteOffset
+function getBitFieldExpression(littleEndian: boolean, byteOffset: number, bitOffset: number, bitCount: number): string {
+    const useBigInt = bitCount > maxSafeBits;
+    const numberConversion = (v: string) => useBigInt ? `BigInt(${v})` : v;
+    const primitive = (v: bigint | number) => useBigInt ? `${v}n` : `${v}`;
+
+    const parts = [];
+    const end = Math.ceil((bitCount + bitOffset) / 8);
+    for (let i = 0; i < end; i++)
+    {
+        const byte = littleEndian ? byteOffset + i : byteOffset + end - 1 - i;
+        const shift = i * 8 - (bitOffset % 8);
+        if (shift == 0) {
+            parts.push(numberConversion(`view.getUint8(${byte})`));
+        } else if (shift < 0) {
+            parts.push(`(${numberConversion(`view.getUint8(${byte})`)} >> ${primitive(-shift)})`);
+        } else {
+            parts.push(`(${numberConversion(`view.getUint8(${byte})`)} << ${primitive(shift)})`);
+        }
+    }
+
+    const mask = (1n << BigInt(bitCount)) - 1n;
+    return `((${parts.join(" | ")}) & ${primitive(mask)})`;
 }
 
-async function parseData<T>(recordIdSize: number, blocks: AsyncIterableIterator<DataView<ArrayBuffer>>, records: ReadonlyMap<number, T & {length: number}>, rowHandler: (context: T, chunk: DataView) => boolean): Promise<void> {
+async function parseData<T>(recordIdSize: number, blocks: AsyncIterableIterator<DataView<ArrayBuffer>>, records: ReadonlyMap<number, T & {length: number}>, rowHandler: (context: T, chunk: DataView) => boolean): Promise<number> {
     const carry = new Uint8Array(recordIdSize + Math.max(...Array.from(records.values()).map(x => x.length)));
     let carryLength = 0;
 
@@ -252,7 +314,7 @@ async function parseData<T>(recordIdSize: number, blocks: AsyncIterableIterator<
             }
             buffer = buffer.subarray(recordIdSize); // Consume the record ID
             if (rowHandler(metadata, new DataView(buffer.buffer, buffer.byteOffset, metadata.length))) {
-                return;
+                return 0;
             }
             buffer = buffer.subarray(metadata.length); // Consume the record data
         }
@@ -262,4 +324,6 @@ async function parseData<T>(recordIdSize: number, blocks: AsyncIterableIterator<
             carryLength += buffer.length;
         }
     }
+    // Anything still carried once the blocks run out is an incomplete trailing record
+    return carryLength;
 }
@@ -1,16 +1,22 @@
-import { describe, it, expect } from 'vitest';
+import { describe, it, expect, vi } from 'vitest';
+import { BufferedFileReader } from './bufferedFileReader';
 import { openMdfFile } from './mdfFile';
 import { ChannelType } from './decoder';
 import { SerializeContext } from './v4/serializer';
-import { resolveHeaderOffset } from './v4/headerBlock';
+import { resolveHeaderOffset, TimeFlags } from './v4/headerBlock';
 import type { Header } from './v4/headerBlock';
+import type { FileHistoryBlock } from './v4/fileHistoryBlock';
 import type { DataGroupBlock } from './v4/dataGroupBlock';
 import type { ChannelGroupBlock } from './v4/channelGroupBlock';
 import { DataType, type ChannelBlock } from './v4/channelBlock';
 import type { TextBlock } from './v4/textBlock';
 import type { DataTableBlock } from './v4/dataTableBlock';
+import type { DataListBlock } from './v4/dataListBlock';
+import { ConversionType, type ChannelConversionBlock } from './v4/channelConversionBlock';
+import { deserializeConversion } from './conversion';
+import { BusType, SourceType, type SourceInformationBlock } from './v4/sourceInformationBlock';
 
-async function createMdf4File(groups: { name: string; channels: { name: string; type: 'time' | 'signal'; dataType: DataType; bitCount: number; values: number[] }[] }[]): Promise<File> {
+async function createMdf4File(groups: { name: string; channels: { name: string; type: 'time' | 'signal'; dataType: DataType; bitCount: number; values: number[]; source?: SourceInformationBlock<'instanced'>; conversion?: ChannelConversionBlock<'instanced'>; unit?: TextBlock }[]; blockSizes?: number[]; source?: SourceInformationBlock<'instanced'> }[], headerFields: Partial<Header<'instanced'>> = {}): Promise<File> {
     const context = new SerializeContext();
 
     let lastDataGroup: DataGroupBlock<'instanced'> | null = null;
@@ -30,14 +36,21 @@ async function createMdf4File(groups: { name: string; channels: { name: string;
 
             for (let i = 0; i < channel.values.length; i++) {
                 const offset = i * recordSize + byteOffset;
-                if (channel.bitCount === 64) {
-                    dataView.setFloat64(offset, channel.values[i], true);
+                const value = channel.values[i];
+                if (channel.dataType === DataType.FloatLe) {
+                    if (channel.bitCount === 64) {
+                        dataView.setFloat64(offset, value, true);
+                    } else {
+                        dataView.setFloat32(offset, value, true);
+                    }
+                } else if (channel.bitCount === 64) {
+                    dataView.setBigUint64(offset, BigInt.asUintN(64, BigInt(value)), true);
                 } else if (channel.bitCount === 32) {
-                    dataView.setFloat32(offset, channel.values[i], true);
+                    dataView.setUint32(offset, value >>> 0, true);
                 } else if (channel.bitCount === 16) {
-                    dataView.setUint16(offset, channel.values[i], true);
+                    dataView.setUint16(offset, value & 0xFFFF, true);
                 } else {
-                    dataView.setUint8(offset, channel.values[i]);
+                    dataView.setUint8(offset, value & 0xFF);
                 }
             }
 
@@ -45,10 +58,10 @@ async function createMdf4File(groups: { name: string; channels: { name: string;
                 channelNext: lastChannel,
                 component: null,
                 txName: channelName,
-                siSource: null,
-                conversion: null,
+                siSource: channel.source ?? null,
+                conversion: channel.conversion ?? null,
                 data: null,
-                unit: null,
+                unit: channel.unit ?? null,
                 comment: null,
                 channelType: channel.type === 'time' ? 2 : 0,
                 syncType: 0,
@@ -72,13 +85,23 @@ async function createMdf4File(groups: { name: string; channels: { name: string;
             byteOffset += byteSize;
         }
 
-        const dataTable: DataTableBlock = { data: dataView };
+        // Optionally split the records across a data list of arbitrarily sized data tables
+        let data: DataTableBlock | DataListBlock<'instanced'> = { data: dataView };
+        if (group.blockSizes) {
+            const dataTables: DataTableBlock[] = [];
+            let blockStart = 0;
+            for (const size of group.blockSizes) {
+                dataTables.push({ data: new DataView(dataBuffer, blockStart, size) });
+                blockStart += size;
+            }
+            data = { dataListNext: null, data: dataTables, flags: 0 };
+        }
 
         const channelGroup: ChannelGroupBlock<'instanced'> = {
             channelGroupNext: null,
             channelFirst: lastChannel,
             acquisitionName: { data: group.name },
-            acquisitionSource: null,
+            acquisitionSource: group.source ?? null,
             sampleReductionFirst: null,
             comment: null,
             recordId: 0n,
@@ -92,7 +115,7 @@ async function createMdf4File(groups: { name: string; channels: { name: string;
         const dataGroup: DataGroupBlock<'instanced'> = {
             dataGroupNext: lastDataGroup,
             channelGroupFirst: channelGroup,
-            data: dataTable,
+            data,
             comment: null,
             recordIdSize: 0,
         };
@@ -115,6 +138,7 @@ async function createMdf4File(groups: { name: string; channels: { name: string;
         flags: 0,
         startAngle: 0n,
         startDistance: 0n,
+        ...headerFields,
     };
 
     resolveHeaderOffset(context, header);
@@ -166,8 +190,12 @@ describe('mdfFile v4', () => {
         const groups = mdf.getGroups();
         expect(groups.length).toBe(1);
 
-        const channels = groups[0].channelGroups[0].channels;
+        const channelGroup = groups[0].channelGroups[0];
+        expect(channelGroup.rowCount).toBe(5);
+
+        const channels = channelGroup.channels;
         expect(channels.length).toBe(2);
+        expect(channels.every(c => c.channelGroup === channelGroup)).toBe(true);
 
         const timeChannel = channels.find(c => c.channelType === ChannelType.Time);
         const valueChannel = channels.find(c => c.channelType === ChannelType.Signal);
@@ -178,6 +206,150 @@ describe('mdfFile v4', () => {
         expect(valueChannel!.name).toBe('Signal1');
     });
 
+    it('should read the start time and UTC offset', async () => {
+        const file = await createMdf4File([], {
+            startTime: 1_700_000_000_000_000_000n,
+            timeZone: -300,
+            dstOffset: 60,
+            timeFlags: TimeFlags.OffsetsValid,
+        });
+
+        const mdf = await openMdfFile(file);
+        expect(mdf.startTime).toBe(1_700_000_000);
+        expect(mdf.utcOffset).toBe(-240);
+        expect(mdf.isLocalTime).toBe(false);
+    });
+
+    it('should report local start times unshifted', async () => {
+        const file = await createMdf4File([], {
+            startTime: 1_700_000_000_000_000_000n,
+            timeZone: 60,
+            timeFlags: TimeFlags.LocalTime | TimeFlags.OffsetsValid,
+        });
+
+        const mdf = await openMdfFile(file);
+        expect(mdf.startTime).toBe(1_700_000_000);
+        expect(mdf.utcOffset).toBeUndefined();
+        expect(mdf.isLocalTime).toBe(true);
+    });
+
+    it('should leave the UTC offset undefined when the header offsets are not valid', async () => {
+        const file = await createMdf4File([], { startTime: 1_700_000_000_000_000_000n, timeZone: 60 });
+
+        const mdf = await openMdfFile(file);
+        expect(mdf.startTime).toBe(1_700_000_000);
+        expect(mdf.utcOffset).toBeUndefined();
+    });
+
+    it('should read the header comment', async () => {
+        const comment = '<HDcomment><TX>Test drive</TX></HDcomment>';
+        const file = await createMdf4File([], { comment: { data: comment } });
+
+        const mdf = await openMdfFile(file);
+        expect(await mdf.getComment()).toBe(comment);
+    });
+
+    it('should return a null comment when the header has none', async () => {
+        const mdf = await openMdfFile(await createMdf4File([]));
+        expect(await mdf.getComment()).toBeNull();
+    });
+
+    it('should open a file whose header comment link points at the wrong block', async () => {
+        const bytes = new Uint8Array(await (await createMdf4File([])).arrayBuffer());
+        // Point the header's comment link (its sixth link) back at the header block itself
+        new DataView(bytes.buffer).setBigUint64(64 + 24 + 5 * 8, 64n, true);
+
+        const mdf = await openMdfFile(new File([bytes], 'test.mf4'));
+        await expect(mdf.getComment()).rejects.toThrow('Invalid block tag');
+    });
+
+    it('should read the file history', async () => {
+        const file = await createMdf4File([], {
+            fileHistory: {
+                fileHistoryNext: {
+                    fileHistoryNext: null,
+                    comment: { data: '<FHcomment><TX>Converted</TX></FHcomment>' },
+                    time: 1_700_000_100_000_000_000n,
+                    timeZone: -300,
+                    dstOffset: 60,
+                    timeFlags: TimeFlags.OffsetsValid,
+                },
+                comment: { data: '<FHcomment><TX>Created</TX></FHcomment>' },
+                time: 1_700_000_000_000_000_000n,
+                timeZone: 0,
+                dstOffset: 0,
+                timeFlags: 0,
+            },
+        });
+        const mdf = await openMdfFile(file);
+        expect(await mdf.getFileHistory()).toEqual([
+            { time: 1_700_000_000, utcOffset: undefined, isLocalTime: false, comment: '<FHcomment><TX>Created</TX></FHcomment>' },
+            { time: 1_700_000_100, utcOffset: -240, isLocalTime: false, comment: '<FHcomment><TX>Converted</TX></FHcomment>' },
+        ]);
+    });
+
+    it('should report local file history times unshifted', async () => {
+        const file = await createMdf4File([], {
+            fileHistory: {
+                fileHistoryNext: null,
+                comment: null,
+                time: 1_700_000_000_000_000_000n,
+                timeZone: 60,
+                dstOffset: 0,
+                timeFlags: TimeFlags.LocalTime | TimeFlags.OffsetsValid,
+            },
+        });
+
+        const mdf = await openMdfFile(file);
+        expect(await mdf.getFileHistory()).toEqual([
+            { time: 1_700_000_000, utcOffset: undefined, isLocalTime: true, comment: null },
+        ]);
+    });
+
+    it('should read file history comments stored as plain text', async () => {
+        const file = await createMdf4File([], {
+            fileHistory: {
+                fileHistoryNext: null,
+                comment: { data: 'Created' },
+                time: 1_700_000_000_000_000_000n,
+                timeZone: 0,
+                dstOffset: 0,
+                timeFlags: 0,
+            },
+        });
+        // The writer always stores comments as ##MD, so retag the only one as ##TX
+        const bytes = new Uint8Array(await file.arrayBuffer());
+        const tag = String.fromCharCode(...bytes).indexOf('##MD');
+        bytes.set(new TextEncoder().encode('##TX'), tag);
+
+        const mdf = await openMdfFile(new File([bytes], 'test.mf4'));
+        expect((await mdf.getFileHistory())[0].comment).toBe('Created');
+    });
+
+    it('should open a file whose file history chain links back on itself', async () => {
+        const fileHistory: FileHistoryBlock<'instanced'> = {
+            fileHistoryNext: null,
+            comment: null,
+            time: 1_700_000_000_000_000_000n,
+            timeZone: 0,
+            dstOffset: 0,
+            timeFlags: 0,
+        };
+        fileHistory.fileHistoryNext = fileHistory;
+        const file = await createMdf4File([], { fileHistory });
+
+        const mdf = await openMdfFile(file);
+        await expect(mdf.getFileHistory()).rejects.toThrow('Circular reference in file history');
+    });
+
+    it('should reject files that are not MDF files', async () => {
+        const truncated = new File([new Uint8Array(16)], 'truncated.mf4');
+        await expect(openMdfFile(truncated)).rejects.toThrow('Invalid length ID header');
+
+        const garbage = new File([new Uint8Array(64).fill(0x41)], 'garbage.mf4');
+        await expect(openMdfFile(garbage)).rejects.toThrow('Invalid ID header');
+    });
+
     it('should read signal data', async () => {
         const timeValues = [0, 0.5, 1.0, 1.5, 2.0];
         const signalValues = [100, 200, 300, 400, 500];
@@ -215,6 +387,140 @@ describe('mdfFile v4', () => {
         }
     });
 
+    it('should read integer signal data', async () => {
+        const timeValues = [0, 0.1, 0.2, 0.3];
+        const unsignedValues = [0, 1, 255, 65535];
+        const signedValues = [-32768, -1, 0, 32767];
+        const signed32Values = [-2147483648, -5, 5, 2147483647];
+
+        const file = await createMdf4File([
+            {
+                name: 'TestGroup',
+                channels: [
+                    { name: 'Time', type: 'time', dataType: DataType.FloatLe, bitCount: 64, values: timeValues },
+                    { name: 'Unsigned', type: 'signal', dataType: DataType.UintLe, bitCount: 16, values: unsignedValues },
+                    { name: 'Signed', type: 'signal', dataType: DataType.IntLe, bitCount: 16, values: signedValues },
+                    { name: 'Signed32', type: 'signal', dataType: DataType.IntLe, bitCount: 32, values: signed32Values },
+                ],
+            },
+        ]);
+
+        const mdf = await openMdfFile(file);
+        const channels = mdf.getGroups()[0].channelGroups[0].channels;
+        const buffers = new Map(['Time', 'Unsigned', 'Signed', 'Signed32'].map(name => [name, makeBuffer()] as const));
+
+        await mdf.read(Array.from(buffers, ([name, buffer]) => ({ channel: channels.find(c => c.name === name)!, buffer })));
+
+        expect(buffers.get('Time')!.values).toEqual(timeValues);
+        expect(buffers.get('Unsigned')!.values).toEqual(unsignedValues);
+        expect(buffers.get('Signed')!.values).toEqual(signedValues);
+        expect(buffers.get('Signed32')!.values).toEqual(signed32Values);
+    });
+
+    it('should read records split across unequal data list blocks', async () => {
+        const timeValues = [0, 1, 2, 3, 4];
+        const signalValues = [10, 20, 30, 40, 50];
+
+        // Records are 16 bytes long, so these blocks split records part way through
+        const file = await createMdf4File([
+            {
+                name: 'Group1',
+                channels: [
+                    { name: 'Time', type: 'time', dataType: DataType.FloatLe, bitCount: 64, values: timeValues },
+                    { name: 'Signal', type: 'signal', dataType: DataType.FloatLe, bitCount: 64, values: signalValues },
+                ],
+                blockSizes: [20, 3, 57],
+            },
+        ]);
+
+        const mdf = await openMdfFile(file);
+        const channels = mdf.getGroups()[0].channelGroups[0].channels;
+        const timeBuf = makeBuffer();
+        const signalBuf = makeBuffer();
+
+        await mdf.read([
+            { channel: channels.find(c => c.name === 'Time')!, buffer: timeBuf },
+            { channel: channels.find(c => c.name === 'Signal')!, buffer: signalBuf },
+        ]);
+
+        expect(timeBuf.values).toEqual(timeValues);
+        expect(signalBuf.values).toEqual(signalValues);
+    });
+
+    it('should read channel sources', async () => {
+        const busSource: SourceInformationBlock<'instanced'> = { txName: { data: 'CAN1' }, txPath: { data: 'Vehicle/CAN1' }, comment: null, sourceType: SourceType.Bus, busType: BusType.Can, flags: 0 };
+        const ecuSource: SourceInformationBlock<'instanced'> = { txName: { data: 'ECU' }, txPath: null, comment: null, sourceType: SourceType.Ecu, busType: BusType.None, flags: 0 };
+
+        const file = await createMdf4File([
+            {
+                name: 'WithSource',
+                channels: [
+                    { name: 'Time', type: 'time', dataType: DataType.FloatLe, bitCount: 64, values: [0] },
+                    { name: 'Speed', type: 'signal', dataType: DataType.FloatLe, bitCount: 64, values: [1], source: busSource },
+                ],
+                source: ecuSource,
+            },
+            {
+                name: 'WithoutSource',
+                channels: [
+                    { name: 'Time', type: 'time', dataType: DataType.FloatLe, bitCount: 64, values: [0] },
+                ],
+            },
+        ]);
+
+        const mdf = await openMdfFile(file);
+        const channelGroups = mdf.getGroups().map(g => g.channelGroups[0]);
+        const withSource = channelGroups.find(cg => cg.name === 'WithSource')!;
+        const withoutSource = channelGroups.find(cg => cg.name === 'WithoutSource')!;
+
+        expect(await withSource.channels.find(c => c.name === 'Speed')!.getSource())
+            .toEqual({ name: 'CAN1', path: 'Vehicle/CAN1', sourceType: SourceType.Bus, busType: BusType.Can });
+        // Channels without their own source don't inherit the group's acquisition source
+        expect(await withSource.channels.find(c => c.name === 'Time')!.getSource()).toBeNull();
+        expect(await withSource.getSource())
+            .toEqual({ name: 'ECU', path: null, sourceType: SourceType.Ecu, busType: BusType.None });
+        expect(await withoutSource.getSource()).toBeNull();
+        expect(await withoutSource.channels[0].getSource()).toBeNull();
+    });
+
+    it('should read the inverse conversion', async () => {
+        const linear = (intercept: number, slope: number, inverse: ChannelConversionBlock<'instanced'> | null): ChannelConversionBlock<'instanced'> => ({
+            txName: null,
+            mdUnit: null,
+            mdComment: null,
+            inverse,
+            type: ConversionType.Linear,
+            precision: 0,
+            flags: 0,
+            physicalRangeMinimum: 0,
+            physicalRangeMaximum: 0,
+            values: [intercept, slope],
+            refs: [],
+        });
+
+        const file = await createMdf4File([
+            {
+                name: 'Group',
+                channels: [
+                    { name: 'Time', type: 'time', dataType: DataType.FloatLe, bitCount: 64, values: [0] },
+                    { name: 'Scaled', type: 'signal', dataType: DataType.FloatLe, bitCount: 64, values: [1], conversion: linear(10, 2, linear(-5, 0.5, null)) },
+                ],
+            },
+        ]);
+
+        const mdf = await openMdfFile(file);
+        const channels = mdf.getGroups()[0].channelGroups[0].channels;
+        const scaled = channels.find(c => c.name === 'Scaled')!;
+
+        const conversion = deserializeConversion(await scaled.getConversion())!;
+        const inverse = deserializeConversion(await scaled.getInverseConversion())!;
+        expect(conversion(10)).toBe(30);
+        expect(inverse(30)).toBe(10);
+
+        const time = channels.find(c => c.name === 'Time')!;
+        expect((await time.getInverseConversion()).conversion).toBeNull();
+    });
+
     it('should handle multiple groups', async () => {
         const file = await createMdf4File([
             {
@@ -296,6 +602,35 @@ describe('mdfFile v4', () => {
         expect(signalBuf.values).toEqual([10, 20, 30]);
     });
 
+    it('should read a unit shared between channels only once', async () => {
+        // The same object is written once, so both channels link to the same text block
+        const unit: TextBlock = { data: 'V' };
+        const file = await createMdf4File([
+            {
+                name: 'Group',
+                channels: [
+                    { name: 'Time', type: 'time', dataType: DataType.FloatLe, bitCount: 64, values: [0] },
+                    { name: 'A', type: 'signal', dataType: DataType.FloatLe, bitCount: 64, values: [1], unit },
+                    { name: 'B', type: 'signal', dataType: DataType.FloatLe, bitCount: 64, values: [2], unit },
+                ],
+            },
+        ]);
+
+        const mdf = await openMdfFile(file);
+        const channels = mdf.getGroups()[0].channelGroups[0].channels;
+        const readBytes = vi.spyOn(BufferedFileReader.prototype, 'readBytes');
+        try {
+            expect(await channels.find(c => c.name === 'A')!.getUnit()).toBe('V');
+            const readsForFirstUnit = readBytes.mock.calls.length;
+            expect(readsForFirstUnit).toBeGreaterThan(0);
+
+            expect(await channels.find(c => c.name === 'B')!.getUnit()).toBe('V');
+            expect(readBytes.mock.calls.length).toBe(readsForFirstUnit);
+        } finally {
+            readBytes.mockRestore();
+        }
+    });
+
     it('benchmark: read 1 signal from group with 100 channels', async () => {
         const rowCount = 10_000;
         const channelCount = 100;
@@ -336,3 +671,50 @@ describe('mdfFile v4', () => {
         expect(duration).toBeGreaterThan(0);
     });
 });
+
+// Builds an empty little endian MDF3 file, optionally with the 3.2 start time and UTC offset header fields
+function createMdf3File(startTime?: bigint, utcOffset?: number): File {
+    const headerLength = 4 + 160 + (startTime !== undefined ? 8 : 0) + (utcOffset !== undefined ? 2 : 0);
+    const bytes = new Uint8Array(64 + headerLength);
+    const view = new DataView(bytes.buffer);
+    const encoder = new TextEncoder();
+
+    bytes.set(encoder.encode('MDF     3.30    test    '), 0);
+    view.setUint16(24, 0, true); // Little endian
+    view.setUint16(28, 330, true);
+
+    bytes.set(encoder.encode('HD'), 64);
+    view.setUint16(66, headerLength, true);
+    // Links, data group count, date, time, author, organization, project and subject are left empty
+    if (startTime !== undefined) {
+        view.setBigUint64(64 + 4 + 160, startTime, true);
+    }
+    if (utcOffset !== undefined) {
+        view.setInt16(64 + 4 + 168, utcOffset, true);
+    }
+
+    return new File([bytes], 'test.mdf');
+}
+
+describe('mdfFile v3', () => {
+    it('should convert the local start time to UTC using the UTC offset', async () => {
+        const mdf = await openMdfFile(createMdf3File(1_700_000_000_000_000_000n, 2));
+        expect(mdf.version).toBe(330);
+        expect(mdf.utcOffset).toBe(120);
+        expect(mdf.startTime).toBe(1_700_000_000 - 2 * 3600);
+        expect(mdf.isLocalTime).toBe(false);
+    });
+
+    it('should report the local start time unshifted when the header has no offset field', async () => {
+        const mdf = await openMdfFile(createMdf3File(1_700_000_000_000_000_000n));
+        expect(mdf.utcOffset).toBeUndefined();
+        expect(mdf.startTime).toBe(1_700_000_000);
+        expect(mdf.isLocalTime).toBe(true);
+    });
+
+    it('should leave the start time undefined for headers before MDF 3.2', async () => {
+        const mdf = await openMdfFile(createMdf3File());
+        expect(mdf.startTime).toBeUndefined();
+        expect(mdf.utcOffset).toBeUndefined();
+    });
+});
@@ -8,20 +8,48 @@ export interface MdfChannel {
     readonly name: string;
     readonly channelType: ChannelType;
     readonly numberType: NumberType;
+    readonly channelGroup: MdfChannelGroup;
     getConversion(): Promise<SerializableConversionData>;
+    /** Conversion from physical values back to raw values, if the file provides one (MDF4 only). */
+    getInverseConversion(): Promise<SerializableConversionData>;
     getUnit(): Promise<string | null>;
+    /**
+     * Source of this channel itself (MDF4 only). This does not fall back to the channel group's acquisition source,
+     * so that the two can be told apart; use channelGroup.getSource() for that.
+     */
+    getSource(): Promise<MdfSource | null>;
+}
+
+export interface MdfSource {
+    readonly name: string | null;
+    readonly path: string | null;
+    readonly sourceType: v4.SourceType;
+    readonly busType: v4.BusType;
 }
 
 export interface MdfChannelGroup {
     readonly name: string | null;
     readonly channels: MdfChannel[];
     readonly rowCount: number;
+    /** Acquisition source of the channel group (MDF4 only). */
+    getSource(): Promise<MdfSource | null>;
 }
 
 export interface MdfDataGroup {
     readonly channelGroups: MdfChannelGroup[];
 }
 
+export interface MdfFileHistoryEntry {
+    /** Time of the change in unix seconds. This is UTC, unless isLocalTime is set. */
+    readonly time: number;
+    /** Offset of the local time from UTC in minutes (including DST), or undefined if unknown. */
+    readonly utcOffset?: number | undefined;
+    /** True if time is local time with an unknown offset from UTC. */
+    readonly isLocalTime: boolean;
+    /** Comment describing the change (usually XML naming the tool and user), or null if the entry has none. */
+    readonly comment: string | null;
+}
+
 export interface GrowableBuffer<TBuffer> {
     push(value: number | bigint): void;
     getBuffer(): TBuffer;
@@ -30,6 +58,8 @@ export interface GrowableBuffer<TBuffer> {
 
 export interface ReadOptions {
     onProgress?: () => void;
+    /** Called with the number of trailing bytes discarded when a data group ends part way through a record. */
+    onTruncatedRecord?: (byteCount: number) => void;
 }
 
 export interface OpenOptions {
@@ -39,8 +69,19 @@ export interface OpenOptions {
 export interface MdfFile {
     readonly filename: string;
     readonly version: number;
-    /** Absolute recording start in unix seconds (UTC), or undefined if the file has none. */
+    /**
+     * Absolute recording start in unix seconds, or undefined if the file has none. This is UTC, unless isLocalTime is
+     * set, in which case it is the local wall-clock time reported unshifted.
+     */
     readonly startTime?: number | undefined;
+    /** Offset of the recording's local time from UTC in minutes (including DST), or undefined if unknown. */
+    readonly utcOffset?: number | undefined;
+    /** True if startTime is local time with an unknown offset from UTC. */
+    readonly isLocalTime: boolean;
+    /** Comment attached to the file header (usually XML for MDF4), or null if the file has none. */
+    getComment(): Promise<string | null>;
+    /** File history entries in the order they were added (MDF4 only, empty for MDF3). */
+    getFileHistory(): Promise<MdfFileHistoryEntry[]>;
     getGroups(): MdfDataGroup[];
     read(
         channels: Array<{ channel: MdfChannel; buffer: { push(value: number | bigint): void } }>,
@@ -54,6 +95,7 @@ interface LazySignal {
     channel: AbstractChannel;
     conversionLink: number | bigint;
     unitLink: number | bigint;
+    sourceLink: number | bigint;
 }
 
 interface CachedGroup {
@@ -69,6 +111,7 @@ class MdfChannelImpl implements MdfChannel {
     readonly channelGroup: MdfChannelGroupImpl;
     private mdf: MdfFileImpl;
     private cachedConversion: SerializableConversionData | null = null;
+    private cachedInverseConversion: SerializableConversionData | null = null;
 
     constructor(lazy: LazySignal, mdf: MdfFileImpl, channelGroup: MdfChannelGroupImpl) {
         this.name = lazy.name;
@@ -86,13 +129,24 @@ class MdfChannelImpl implements MdfChannel {
         return this.cachedConversion;
     }
 
+    async getInverseConversion(): Promise<SerializableConversionData> {
+        if (!this.cachedInverseConversion) {
+            this.cachedInverseConversion = await this.mdf.loadInverseConversion(this.lazy.conversionLink);
+        }
+        return this.cachedInverseConversion;
+    }
+
     async getUnit(): Promise<string | null> {
         if (this.lazy.unitLink !== 0 && this.lazy.unitLink !== 0n) {
-            return this.mdf.loadTextBlock(this.lazy.unitLink);
+            return this.mdf.loadUnit(this.lazy.unitLink);
         }
         const conversion = await this.getConversion();
         return conversion.unit;
     }
+
+    async getSource(): Promise<MdfSource | null> {
+        return this.mdf.loadSource(this.lazy.sourceLink);
+    }
 }
 
 class MdfChannelGroupImpl implements MdfChannelGroup {
@@ -102,7 +156,35 @@ class MdfChannelGroupImpl implements MdfChannelGroup {
         public readonly dataGroup: MdfDataGroupImpl,
         public readonly name: string | null,
         public readonly rowCount: number,
+        private readonly sourceLink: number | bigint,
+        private readonly mdf: MdfFileImpl,
     ) {}
+
+    async getSource(): Promise<MdfSource | null> {
+        return this.mdf.loadSource(this.sourceLink);
+    }
+}
+
+// Counts loaded signals, throttling updates so large files don't flood the caller with progress callbacks
+class SignalProgress {
+    private count = 0;
+    private lastUpdate = 0;
+
+    constructor(private readonly onProgress?: (signalCount: number) => void) {}
+
+    add(): void {
+        if (!this.onProgress) return;
+        this.count++;
+        const now = performance.now();
+        if (now - this.lastUpdate > 100) {
+            this.onProgress(this.count);
+            this.lastUpdate = now;
+        }
+    }
+
+    finish(): void {
+        this.onProgress?.(this.count);
+    }
 }
 
 class MdfDataGroupImpl implements MdfDataGroup {
@@ -114,8 +196,15 @@ class MdfFileImpl implements MdfFile {
     readonly filename: string;
     readonly version: number;
     startTime?: number | undefined;
+    utcOffset?: number | undefined;
+    isLocalTime = false;
+    private commentLink: v3.Link<v3.TextBlock> | v4.Link<v4.TextBlock | v4.MetadataBlock> | null = null;
+    private fileHistoryLink: v4.Link<v4.FileHistoryBlock> | null = null;
     private dataGroups: MdfDataGroupImpl[] = [];
     private reader: BufferedFileReader;
+    // Units are commonly shared between many channels, so cache text by link to avoid re-reading the same block
+    private unitCache = new Map<bigint, string | null>();
+    private sourceCache = new Map<bigint, MdfSource>();
 
     private constructor(reader: BufferedFileReader) {
         this.reader = reader;
@@ -155,11 +244,19 @@ class MdfFileImpl implements MdfFile {
 
         if (header.startTime !== undefined && header.startTime !== 0n) {
             this.startTime = Number(header.startTime) / 1e9;
+            if (header.utcOffset !== undefined) {
+                // MDF3 stores the start time as local time, with the offset to UTC given in hours
+                this.utcOffset = header.utcOffset * 60;
+                this.startTime -= this.utcOffset * 60;
+            } else {
+                this.isLocalTime = true;
+            }
         }
 
+        this.commentLink = header.fileComment;
+
         let dgLink = header.firstDataGroup as v3.Link<v3.DataGroupBlock>;
-        let totalSignalCount = 0;
-        let lastProgressUpdate = 0;
+        const progress = new SignalProgress(onProgress);
 
         while (v3.isNonNullLink(dgLink)) {
             const dgBlockLink = dgLink;
@@ -172,7 +269,7 @@ class MdfFileImpl implements MdfFile {
             for await (const channelGroup of v3.iterateChannelGroupBlocks(dgBlock.channelGroupFirst, this.reader)) {
                 totalRows += channelGroup.numberOfRecords;
                 const groupChannels: AbstractChannel[] = [];
-                const cgImpl = new MdfChannelGroupImpl(dgImpl, null, channelGroup.numberOfRecords);
+                const cgImpl = new MdfChannelGroupImpl(dgImpl, null, channelGroup.numberOfRecords, 0, this);
 
                 for await (const channel of v3.iterateChannelBlocks(channelGroup.channelFirst, this.reader)) {
                     const name = channel.longName && v3.isNonNullLink(channel.longName)
@@ -197,17 +294,11 @@ class MdfFileImpl implements MdfFile {
                         channel: abstractChannel,
                         conversionLink: v3.getLink(channel.conversion),
                         unitLink: 0,
+                        sourceLink: 0,
                     };
                     cgImpl.channels.push(new MdfChannelImpl(lazy, this, cgImpl));
 
-                    if (onProgress) {
-                        totalSignalCount++;
-                        const now = performance.now();
-                        if (now - lastProgressUpdate > 100) {
-                            onProgress(totalSignalCount);
-                            lastProgressUpdate = now;
-                        }
-                    }
+                    progress.add();
                 }
 
                 dgImpl.channelGroups.push(cgImpl);
@@ -229,9 +320,7 @@ class MdfFileImpl implements MdfFile {
             dgLink = dgBlock.dataGroupNext;
         }
 
-        if (onProgress) {
-            onProgress(totalSignalCount);
-        }
+        progress.finish();
     }
 
     private async loadGroupsV4(onProgress?: (signalCount: number) => void): Promise<void> {
@@ -239,12 +328,20 @@ class MdfFileImpl implements MdfFile {
         const header = await v4.readHeader(rootLink, this.reader);
 
         if (header.startTime !== 0n) {
+            // Without the local time flag the start time is UTC. With it, the start time is local time with an unknown
+            // offset, so it is reported as-is.
             this.startTime = Number(header.startTime) / 1e9;
+            this.isLocalTime = (header.timeFlags & v4.TimeFlags.LocalTime) !== 0;
+            if (!this.isLocalTime && (header.timeFlags & v4.TimeFlags.OffsetsValid) !== 0) {
+                this.utcOffset = header.timeZone + header.dstOffset;
+            }
         }
 
+        this.commentLink = header.comment;
+        this.fileHistoryLink = header.fileHistory;
+
         let dgLink = header.firstDataGroup as v4.Link<v4.DataGroupBlock>;
-        let totalSignalCount = 0;
-        let lastProgressUpdate = 0;
+        const progress = new SignalProgress(onProgress);
 
         while (v4.isNonNullLink(dgLink)) {
             const dgBlockLink = dgLink;
@@ -256,7 +353,13 @@ class MdfFileImpl implements MdfFile {
             for await (const channelGroup of v4.iterateChannelGroupBlocks(dgBlock.channelGroupFirst, this.reader)) {
                 const cgName = (await v4.readTextBlock(channelGroup.acquisitionName, this.reader))?.data ?? null;
                 const groupChannels: AbstractChannel[] = [];
-                const cgImpl = new MdfChannelGroupImpl(dgImpl, cgName, Number(channelGroup.cycleCount));
+                const cgImpl = new MdfChannelGroupImpl(
+                    dgImpl,
+                    cgName,
+                    Number(channelGroup.cycleCount),
+                    v4.getLink(channelGroup.acquisitionSource),
+                    this,
+                );
 
                 for await (const channel of v4.iterateChannelBlocks(channelGroup.channelFirst, this.reader)) {
                     const channelName = (await v4.readTextBlock(channel.txName, this.reader))?.data ?? "";
@@ -279,17 +382,11 @@ class MdfFileImpl implements MdfFile {
                         channel: abstractChannel,
                         conversionLink: v4.getLink(channel.conversion as v4.Link<unknown>),
                         unitLink: v4.getLink(channel.unit as v4.Link<unknown>),
+                        sourceLink: v4.getLink(channel.siSource),
                     };
                     cgImpl.channels.push(new MdfChannelImpl(lazy, this, cgImpl));
 
-                    if (onProgress) {
-                        totalSignalCount++;
-                        const now = performance.now();
-                        if (now - lastProgressUpdate > 100) {
-                            onProgress(totalSignalCount);
-                            lastProgressUpdate = now;
-                        }
-                    }
+                    progress.add();
                 }
 
                 dgImpl.channelGroups.push(cgImpl);
@@ -311,9 +408,38 @@ class MdfFileImpl implements MdfFile {
             dgLink = dgBlock.dataGroupNext as v4.Link<v4.DataGroupBlock>;
         }
 
-        if (onProgress) {
-            onProgress(totalSignalCount);
+        progress.finish();
+    }
+
+    async getComment(): Promise<string | null> {
+        if (this.commentLink === null) return null;
+        if (this.version >= 400 && this.version < 500) {
+            // The header comment may be stored as either plain text or XML metadata
+            const block = await v4.readTextOrMetadataBlock(this.commentLink as v4.Link<v4.TextBlock | v4.MetadataBlock>, this.reader);
+            return block?.data ?? null;
+        } else {
+            const block = await v3.readTextBlock(this.commentLink as v3.Link<v3.TextBlock>, this.reader);
+            return block?.data ?? null;
+        }
+    }
+
+    async getFileHistory(): Promise<MdfFileHistoryEntry[]> {
+        const entries: MdfFileHistoryEntry[] = [];
+        if (this.fileHistoryLink === null) return entries;
+        for await (const fileHistory of v4.iterateFileHistoryBlocks(this.fileHistoryLink, this.reader)) {
+            // History times use the same flags as the header start time
+            const isLocalTime = (fileHistory.timeFlags & v4.TimeFlags.LocalTime) !== 0;
+            const comment = await v4.readTextOrMetadataBlock(fileHistory.comment, this.reader);
+            entries.push({
+                time: Number(fileHistory.time) / 1e9,
+                utcOffset: !isLocalTime && (fileHistory.timeFlags & v4.TimeFlags.OffsetsValid) !== 0
+                    ? fileHistory.timeZone + fileHistory.dstOffset
+                    : undefined,
+                isLocalTime,
+                comment: comment?.data ?? null,
+            });
         }
+        return entries;
     }
 
     async loadConversion(conversionLink: number | bigint): Promise<SerializableConversionData> {
@@ -324,11 +450,44 @@ class MdfFileImpl implements MdfFile {
         }
     }
 
-    async loadTextBlock(link: number | bigint): Promise<string | null> {
+    async loadInverseConversion(conversionLink: number | bigint): Promise<SerializableConversionData> {
+        if (this.version >= 400 && this.version < 500 && conversionLink !== 0n) {
+            const conversion = await v4.readConversionBlock(v4.newNonNullLink(conversionLink as bigint), this.reader);
+            return this.loadConversionV4(v4.getLink(conversion.inverse));
+        }
+        return { conversion: null, textValues: [], unit: null };
+    }
+
+    async loadUnit(link: number | bigint): Promise<string | null> {
+        if (this.version >= 400 && this.version < 500) {
+            if (link === 0n) return null;
+            const cached = this.unitCache.get(link as bigint);
+            if (cached !== undefined) return cached;
+            const block = await v4.readBlock(v4.newNonNullLink(link as bigint), this.reader, ["##TX", "##MD"]);
+            // Units stored as metadata wrap the unit text in XML
+            const unit = block.type === "##MD"
+                ? v4.getMetadataText(v4.deserializeMetadataBlock(block))
+                : v4.deserializeTextBlock(block).data;
+            this.unitCache.set(link as bigint, unit);
+            return unit;
+        }
+        return null;
+    }
+
+    async loadSource(link: number | bigint): Promise<MdfSource | null> {
         if (this.version >= 400 && this.version < 500) {
             if (link === 0n) return null;
-            const block = await v4.readTextBlock(v4.newNonNullLink(link as bigint), this.reader);
-            return block.data;
+            const cached = this.sourceCache.get(link as bigint);
+            if (cached !== undefined) return cached;
+            const block = await v4.readSourceInformationBlock(v4.newNonNullLink(link as bigint), this.reader);
+            const source: MdfSource = {
+                name: (await v4.readTextBlock(block.txName, this.reader))?.data ?? null,
+                path: (await v4.readTextBlock(block.txPath, this.reader))?.data ?? null,
+                sourceType: block.sourceType,
+                busType: block.busType,
+            };
+            this.sourceCache.set(link as bigint, source);
+            return source;
         }
         return null;
     }
@@ -412,7 +571,9 @@ class MdfFileImpl implements MdfFile {
             if (unit.type === "##TX") {
                 block.mdUnit = v4.deserializeTextBlock(unit);
             } else if (unit.type === "##MD") {
-                block.mdUnit = v4.deserializeMetadataBlock(unit);
+                // Keep only the unit text from the XML, so it can be shown directly
+                const text = v4.getMetadataText(v4.deserializeMetadataBlock(unit));
+                block.mdUnit = text === null ? null : { data: text };
             }
         }
 
@@ -445,6 +606,8 @@ class MdfFileImpl implements MdfFile {
             case v4.DataType.IntBe: return DataType.IntBe;
             case v4.DataType.FloatLe: return DataType.FloatLe;
             case v4.DataType.FloatBe: return DataType.FloatBe;
+            case v4.DataType.CanOpenDate: return DataType.CanOpenDate;
+            case v4.DataType.CanOpenTime: return DataType.CanOpenTime;
             default: return DataType.Unknown;
         }
     }
@@ -1,6 +1,7 @@
 import { Link, NonNullLink, isNonNullLink, readBlock, MaybeLinked, GenericBlock } from './common';
-import { resolveTextBlockOffset, TextBlock } from './textBlock';
+import { MetadataBlock, resolveTextBlockOffset, TextBlock } from './textBlock';
 import { ChannelConversionBlock, resolveChannelConversionOffset } from './channelConversionBlock';
+import { resolveSourceInformationOffset, SourceInformationBlock } from './sourceInformationBlock';
 import { SerializeContext, type SerializeWriteFunction } from './serializer';
 import { BufferedFileReader } from '../bufferedFileReader';
 
@@ -35,11 +36,11 @@ export interface ChannelBlock<TMode extends 'linked' | 'instanced' = 'linked'> {
     channelNext: MaybeLinked<ChannelBlock<TMode> | null, TMode>;
     component: MaybeLinked<unknown, TMode>;
     txName: MaybeLinked<TextBlock | null, TMode>;
-    siSource: MaybeLinked<unknown, TMode>;
+    siSource: MaybeLinked<SourceInformationBlock<TMode> | null, TMode>;
     conversion: MaybeLinked<ChannelConversionBlock<TMode> | null, TMode>;
     data: MaybeLinked<unknown, TMode>;
-    unit: MaybeLinked<TextBlock | null, TMode>;
-    comment: MaybeLinked<TextBlock | null, TMode>;
+    unit: MaybeLinked<TextBlock | MetadataBlock | null, TMode>;
+    comment: MaybeLinked<TextBlock | MetadataBlock | null, TMode>;
     channelType: number;
     syncType: number;
     dataType: DataType;
@@ -65,10 +66,10 @@ export function deserializeChannelBlock(block: GenericBlock): ChannelBlock<'link
         channelNext: block.links[0] as Link<ChannelBlock>,
         component: block.links[1] as Link<unknown>,
         txName: block.links[2] as Link<TextBlock>,
-        siSource: block.links[3] as Link<unknown>,
+        siSource: block.links[3] as Link<SourceInformationBlock>,
         conversion: block.links[4] as Link<ChannelConversionBlock>,
         data: block.links[5] as Link<unknown>,
-        unit: block.links[6] as Link<TextBlock>,
+        unit: block.links[6] as Link<TextBlock | MetadataBlock>,
         comment: block.links[7] as Link<unknown>,
         channelType: view.getUint8(0),
         syncType: view.getUint8(1),
@@ -136,6 +137,7 @@ export function resolveChannelOffset(context: SerializeContext, block: ChannelBl
         block => {
             resolveChannelOffset(context, block.channelNext);
             resolveTextBlockOffset(context, block.txName);
+            resolveSourceInformationOffset(context, block.siSource);
             resolveChannelConversionOffset(context, block.conversion);
             resolveTextBlockOffset(context, block.unit);
             resolveTextBlockOffset(context, block.comment);
@@ -0,0 +1,31 @@
+import { describe, it, expect } from 'vitest';
+import { ConversionType, deserializeConversionBlock } from './channelConversionBlock';
+import { newLink, type GenericBlock } from './common';
+
+function createConversionBlock(declaredRefs: number, actualRefs: number): GenericBlock {
+    const view = new DataView(new ArrayBuffer(24));
+    view.setUint8(0, ConversionType.ValueToTextOrScale);
+    view.setUint16(4, declaredRefs, true);
+    view.setUint16(6, 0, true);
+    const links = Array.from({ length: 4 + actualRefs }, () => newLink<unknown>(0n));
+    return {
+        type: "##CC",
+        length: BigInt(24 + links.length * 8 + view.byteLength),
+        linkCount: BigInt(links.length),
+        buffer: view,
+        links,
+    };
+}
+
+describe('channelConversionBlock v4', () => {
+    it('should read the declared references', () => {
+        const conversion = deserializeConversionBlock(createConversionBlock(2, 2));
+        expect(conversion.type).toBe(ConversionType.ValueToTextOrScale);
+        expect(conversion.refs.length).toBe(2);
+    });
+
+    it('should reject a reference count that does not match the links', () => {
+        expect(() => deserializeConversionBlock(createConversionBlock(3, 2)))
+            .toThrow('Invalid number of conversion references: 3 declared, 2 present');
+    });
+});
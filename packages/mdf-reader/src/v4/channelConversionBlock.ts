@@ -73,14 +73,20 @@ export interface ValueRangeToTextOrScale<TMode extends 'linked' | 'instanced' =
 
 export interface TextToValue<TMode extends 'linked' | 'instanced' = 'linked'> {
     type: ConversionType.TextToValue,
-    values: number[];
-    refs: MaybeLinked<TextBlock, TMode>[];
+    values: number[]; // one value per key, followed by the default value
+    refs: MaybeLinked<TextBlock | null, TMode>[];
 }
 
 export interface TextToText<TMode extends 'linked' | 'instanced' = 'linked'> {
     type: ConversionType.TextToText,
     values: [];
-    refs: MaybeLinked<TextBlock, TMode>[];
+    refs: MaybeLinked<TextBlock | null, TMode>[]; // key/output pairs, followed by the default output
+}
+
+export enum ConversionFlags {
+    PrecisionValid = 0x01,
+    PhysicalRangeValid = 0x02,
+    StatusString = 0x04,
 }
 
 export interface ChannelConversionBlockBase<TMode extends 'linked' | 'instanced' = 'linked'> {
@@ -101,6 +107,9 @@ export function deserializeConversionBlock(block: GenericBlock): ChannelConversi
 
     const refsCount = view.getUint16(4, true);
     const valueCount = view.getUint16(6, true);
+    if (block.links.length !== 4 + refsCount) {
+        throw new Error(`Invalid number of conversion references: ${refsCount} declared, ${block.links.length - 4} present`);
+    }
     
     const values = [];
     for (let offset = 24; offset < 24 + valueCount * 8; offset += 8) {
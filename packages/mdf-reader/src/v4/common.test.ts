@@ -0,0 +1,25 @@
+import { describe, it, expect } from 'vitest';
+import { BufferedFileReader } from '../bufferedFileReader';
+import { newNonNullLink, readBlock } from './common';
+import { createBlockFile, testBlockOffset } from './testUtils';
+
+describe('readBlock v4', () => {
+    it('should read a block with a valid length', async () => {
+        const reader = new BufferedFileReader(createBlockFile('##DT', 32n, 0n));
+        const block = await readBlock(newNonNullLink(BigInt(testBlockOffset)), reader, '##DT');
+        expect(block.buffer.byteLength).toBe(8);
+        expect(block.links.length).toBe(0);
+    });
+
+    it('should reject a block shorter than its header', async () => {
+        const reader = new BufferedFileReader(createBlockFile('##DT', 10n, 0n));
+        await expect(readBlock(newNonNullLink(BigInt(testBlockOffset)), reader, '##DT'))
+            .rejects.toThrow('Invalid ##DT block length: 10 is smaller than the 24 byte header and links');
+    });
+
+    it('should reject a block shorter than its links', async () => {
+        const reader = new BufferedFileReader(createBlockFile('##DL', 32n, 2n));
+        await expect(readBlock(newNonNullLink(BigInt(testBlockOffset)), reader, '##DL'))
+            .rejects.toThrow('Invalid ##DL block length: 32 is smaller than the 40 byte header and links');
+    });
+});
@@ -65,6 +65,10 @@ export async function readBlock<T>(link: Link<T> | NonNullLink<T>, reader: Buffe
         return null;
     }
     const header = await readBlockHeader(link, reader, expectedType);
+    const minimumLength = 24n + header.linkCount * 8n;
+    if (header.length < minimumLength) {
+        throw new Error(`Invalid ${header.type} block length: ${header.length} is smaller than the ${minimumLength} byte header and links`);
+    }
     
     const payload = await reader.readBytes(fileOffset + 24, Number(header.length) - 24);
 
@@ -0,0 +1,148 @@
+import { describe, it, expect } from 'vitest';
+import { deserializeConversion } from '../conversion';
+import { serializeConversion } from './conversion';
+import { ConversionFlags, ConversionType, type ChannelConversionBlock } from './channelConversionBlock';
+
+const base = {
+    txName: null,
+    mdUnit: null,
+    mdComment: null,
+    inverse: null,
+    precision: 0,
+    flags: 0,
+    physicalRangeMinimum: 0,
+    physicalRangeMaximum: 0,
+};
+
+function evaluate(conversion: ChannelConversionBlock<'instanced'>): (value: number) => number | string {
+    return deserializeConversion(serializeConversion(conversion))!;
+}
+
+describe('v4 conversion', () => {
+    describe('physical range', () => {
+        const linear: ChannelConversionBlock<'instanced'> = { ...base, type: ConversionType.Linear, values: [-40, 1], refs: [], physicalRangeMinimum: -40, physicalRangeMaximum: 215 };
+
+        it('should expose the physical range when it is marked valid', () => {
+            const data = serializeConversion({ ...linear, flags: ConversionFlags.PhysicalRangeValid });
+            expect(data.physicalRange).toEqual({ minimum: -40, maximum: 215 });
+        });
+
+        it('should ignore the physical range when it is not marked valid', () => {
+            expect(serializeConversion(linear).physicalRange).toBeUndefined();
+        });
+    });
+
+    describe('ValueToTextOrScale', () => {
+        const scale: ChannelConversionBlock<'instanced'> = { ...base, type: ConversionType.Linear, values: [10, 2], refs: [] };
+
+        it('should use the matching text when the value is a key', () => {
+            const fn = evaluate({ ...base, type: ConversionType.ValueToTextOrScale, values: [1, 2], refs: [{ data: 'ON' }, { data: 'OFF' }, scale] });
+            expect(fn(1)).toBe('ON');
+            expect(fn(2)).toBe('OFF');
+        });
+
+        it('should fall back to the default scale when no key matches', () => {
+            const fn = evaluate({ ...base, type: ConversionType.ValueToTextOrScale, values: [1, 2], refs: [{ data: 'ON' }, { data: 'OFF' }, scale] });
+            expect(fn(5)).toBe(20);
+        });
+
+        it('should fall back to the default text when no key matches', () => {
+            const fn = evaluate({ ...base, type: ConversionType.ValueToTextOrScale, values: [1], refs: [{ data: 'ON' }, { data: 'UNKNOWN' }] });
+            expect(fn(1)).toBe('ON');
+            expect(fn(3)).toBe('UNKNOWN');
+        });
+
+        it('should pass the value through when there is no default', () => {
+            const fn = evaluate({ ...base, type: ConversionType.ValueToTextOrScale, values: [1], refs: [{ data: 'ON' }, null] });
+            expect(fn(3)).toBe(3);
+        });
+    });
+
+    describe('ValueRangeToTextOrScale', () => {
+        const scale: ChannelConversionBlock<'instanced'> = { ...base, type: ConversionType.Linear, values: [0, 10], refs: [] };
+
+        it('should use the text or scale of the matching range', () => {
+            const fn = evaluate({ ...base, type: ConversionType.ValueRangeToTextOrScale, values: [0, 9, 10, 19], refs: [{ data: 'LOW' }, scale, { data: 'DEFAULT' }] });
+            expect(fn(0)).toBe('LOW');
+            expect(fn(9)).toBe('LOW');
+            expect(fn(10)).toBe(100);
+            expect(fn(19)).toBe(190);
+        });
+
+        it('should fall back to the default when no range matches', () => {
+            const fn = evaluate({ ...base, type: ConversionType.ValueRangeToTextOrScale, values: [0, 9], refs: [{ data: 'LOW' }, scale] });
+            expect(fn(-1)).toBe(-10);
+            expect(fn(20)).toBe(200);
+        });
+
+        it('should pass the value through when there is no default', () => {
+            const fn = evaluate({ ...base, type: ConversionType.ValueRangeToTextOrScale, values: [0, 9], refs: [{ data: 'LOW' }, null] });
+            expect(fn(20)).toBe(20);
+        });
+    });
+
+    it('should reject conversions that reference themselves', () => {
+        const conversion: ChannelConversionBlock<'instanced'> = { ...base, type: ConversionType.ValueToTextOrScale, values: [1], refs: [{ data: 'ON' }] };
+        conversion.refs.push(conversion);
+        expect(() => serializeConversion(conversion)).toThrow('Circular reference in channel conversion');
+    });
+
+    describe('ValueToValueTableWithInterpolation', () => {
+        // Keys and values are interleaved, and deliberately out of order
+        const fn = evaluate({ ...base, type: ConversionType.ValueToValueTableWithInterpolation, values: [10, 100, 0, 0, 20, 50], refs: [] });
+
+        it('should return the table value at a key', () => {
+            expect(fn(0)).toBe(0);
+            expect(fn(10)).toBe(100);
+            expect(fn(20)).toBe(50);
+        });
+
+        it('should interpolate linearly between keys', () => {
+            expect(fn(5)).toBe(50);
+            expect(fn(15)).toBe(75);
+        });
+
+        it('should clamp to the first and last values outside the table', () => {
+            expect(fn(-5)).toBe(0);
+            expect(fn(25)).toBe(50);
+        });
+    });
+
+    // String channels aren't decoded yet, so the text inputs below are cast to satisfy the numeric conversion signature
+    describe('TextToValue', () => {
+        const fn = evaluate({ ...base, type: ConversionType.TextToValue, values: [1, 2, -1], refs: [{ data: 'LOW' }, { data: 'HIGH' }] });
+
+        it('should map matching text to its value', () => {
+            expect(fn('LOW' as unknown as number)).toBe(1);
+            expect(fn('HIGH' as unknown as number)).toBe(2);
+        });
+
+        it('should fall back to the default value', () => {
+            expect(fn('OTHER' as unknown as number)).toBe(-1);
+        });
+
+        it('should pass numeric samples through unchanged', () => {
+            expect(fn(0)).toBe(0);
+        });
+    });
+
+    describe('TextToText', () => {
+        it('should translate matching text and fall back to the default text', () => {
+            const fn = evaluate({ ...base, type: ConversionType.TextToText, values: [], refs: [{ data: 'A' }, { data: 'Alpha' }, { data: 'B' }, null, { data: 'Unknown' }] });
+            expect(fn('A' as unknown as number)).toBe('Alpha');
+            expect(fn('B' as unknown as number)).toBe('B');
+            expect(fn('C' as unknown as number)).toBe('Unknown');
+        });
+
+        it('should pass numeric samples through without reporting text values', () => {
+            const data = serializeConversion({ ...base, type: ConversionType.TextToText, values: [], refs: [{ data: 'A' }, { data: 'Alpha' }, { data: 'Unknown' }] });
+            expect(deserializeConversion(data)!(0)).toBe(0);
+            expect(data.textValues).toEqual([]);
+        });
+
+        it('should leave unmatched text unchanged without a default', () => {
+            const fn = evaluate({ ...base, type: ConversionType.TextToText, values: [], refs: [{ data: 'A' }, { data: 'Alpha' }, null] });
+            expect(fn('C' as unknown as number)).toBe('C');
+        });
+    });
+});
@@ -1,5 +1,5 @@
 import { TextValue, SerializableConversionData } from '../conversion';
-import { ChannelConversionBlock, ConversionType } from "./channelConversionBlock";
+import { ChannelConversionBlock, ConversionFlags, ConversionType } from "./channelConversionBlock";
 
 export function serializeConversion(conversion: ChannelConversionBlock<'instanced'> | null): SerializableConversionData {
     const textValues: TextValue[] = [];
@@ -12,11 +12,26 @@ export function serializeConversion(conversion: ChannelConversionBlock<'instance
         return varName;
     }
     
+    // Nested conversions can reference each other, so guard against a malformed file recursing forever
+    const serializing = new Set<ChannelConversionBlock<'instanced'>>();
+
     function serialize(conversion: ChannelConversionBlock<'instanced'> | null): string | null {
         if (conversion === null) {
             return null;
         }
-        
+        if (serializing.has(conversion)) {
+            throw new Error('Circular reference in channel conversion');
+        }
+
+        serializing.add(conversion);
+        try {
+            return serializeBlock(conversion);
+        } finally {
+            serializing.delete(conversion);
+        }
+    }
+
+    function serializeBlock(conversion: ChannelConversionBlock<'instanced'>): string | null {
         switch (conversion.type) {
             case ConversionType.OneToOne:
                 return 'value';
@@ -207,8 +222,46 @@ export function serializeConversion(conversion: ChannelConversionBlock<'instance
                 return `(function() { ${rangeChecks.join('\n')} ${defaultCase} })()`;
             }
             
-            case ConversionType.TextToValue:
-            case ConversionType.TextToText:
+            // String channels aren't decoded yet (they load as the number 0), so these text conversions only take effect
+            // once they are. Until then, non-text samples pass through unchanged rather than all mapping to the default.
+            case ConversionType.TextToValue: {
+                const keys = addToContext(conversion.refs.map(ref => ref?.data ?? null));
+                const values = addToContext(conversion.values.slice(0, conversion.refs.length));
+                const defaultValue = addToContext(conversion.values[conversion.refs.length] ?? 0);
+                return `(function() {
+                    if (typeof value !== 'string') return value;
+                    const index = ${keys}.indexOf(value);
+                    return index === -1 ? ${defaultValue} : ${values}[index];
+                })()`;
+            }
+
+            case ConversionType.TextToText: {
+                const keys: (string | null)[] = [];
+                const outputs: (string | null)[] = [];
+                for (let i = 0; i + 1 < conversion.refs.length; i += 2) {
+                    const output = conversion.refs[i + 1];
+                    keys.push(conversion.refs[i]?.data ?? null);
+                    outputs.push(output?.data ?? null);
+                }
+                // A missing output (or default) leaves the input text unchanged
+                const defaultRef = conversion.refs.length % 2 === 1 ? conversion.refs[conversion.refs.length - 1] : null;
+                let defaultCase: string;
+                // The outputs aren't added to textValues, since that would switch the (currently numeric) samples of
+                // these channels to enum rendering
+                if (defaultRef) {
+                    defaultCase = addToContext(defaultRef.data);
+                } else {
+                    defaultCase = 'value';
+                }
+                const keysVar = addToContext(keys);
+                const outputsVar = addToContext(outputs);
+                return `(function() {
+                    if (typeof value !== 'string') return value;
+                    const index = ${keysVar}.indexOf(value);
+                    return index === -1 ? ${defaultCase} : (${outputsVar}[index] ?? value);
+                })()`;
+            }
+
             default:
                 return '0';
         }
@@ -222,5 +275,8 @@ export function serializeConversion(conversion: ChannelConversionBlock<'instance
         conversion: fnBody ? { fnBody: `return ${fnBody};`, context } : null,
         textValues,
         unit: mdUnit && 'data' in mdUnit ? mdUnit.data : null,
+        physicalRange: conversion && (conversion.flags & ConversionFlags.PhysicalRangeValid) !== 0
+            ? { minimum: conversion.physicalRangeMinimum, maximum: conversion.physicalRangeMaximum }
+            : undefined,
     };
 }
@@ -1,11 +1,11 @@
-import { Link, readBlock, MaybeLinked, GenericBlock, NonNullLink } from './common';
+import { Link, readBlock, MaybeLinked, GenericBlock, NonNullLink, isNonNullLink, getLink } from './common';
 import { SerializeContext, type SerializeWriteFunction } from './serializer';
-import { MetadataBlock, resolveMetadataOffset } from './textBlock';
+import { MetadataBlock, resolveMetadataOffset, TextBlock } from './textBlock';
 import { BufferedFileReader } from '../bufferedFileReader';
 
 export interface FileHistoryBlock<TMode extends 'linked' | 'instanced' = 'linked'> {
     fileHistoryNext: MaybeLinked<FileHistoryBlock<TMode> | null, TMode>;
-    comment: MaybeLinked<MetadataBlock | null, TMode>;
+    comment: MaybeLinked<TextBlock | MetadataBlock | null, TMode>;
     time: bigint;
     timeZone: number;
     dstOffset: number;
@@ -16,10 +16,10 @@ export function deserializeFileHistoryBlock(block: GenericBlock): FileHistoryBlo
     const view = block.buffer;
     return {
         fileHistoryNext: block.links[0] as Link<FileHistoryBlock>,
-        comment: block.links[1] as Link<MetadataBlock>,
+        comment: block.links[1] as Link<TextBlock | MetadataBlock>,
         time: view.getBigUint64(0, true),
-        timeZone: view.getUint16(8),
-        dstOffset: view.getUint16(10),
+        timeZone: view.getInt16(8, true),
+        dstOffset: view.getInt16(10, true),
         timeFlags: view.getUint8(12),
     };
 }
@@ -33,8 +33,8 @@ export async function serializeFileHistoryBlock(write: SerializeWriteFunction, c
             view.setBigUint64(0, context.get(fileHistory.fileHistoryNext), true);
             view.setBigUint64(8, context.get(fileHistory.comment), true);
             view.setBigUint64(16, fileHistory.time, true);
-            view.setUint16(24, fileHistory.timeZone, true);
-            view.setUint16(26, fileHistory.dstOffset, true);
+            view.setInt16(24, fileHistory.timeZone, true);
+            view.setInt16(26, fileHistory.dstOffset, true);
             view.setUint8(28, fileHistory.timeFlags);
             view.setUint8(29, 0);
             view.setUint8(30, 0);
@@ -64,3 +64,20 @@ export async function readFileHistoryBlock(link: Link<FileHistoryBlock>, reader:
     const block = await readBlock(link, reader, "##FH");
     return block === null ? null : deserializeFileHistoryBlock(block);
 }
+
+export async function* iterateFileHistoryBlocks(startLink: Link<FileHistoryBlock>, reader: BufferedFileReader): AsyncIterableIterator<FileHistoryBlock<'linked'>> {
+    let currentLink = startLink;
+    // A malformed file can link the chain back on itself, so stop rather than loop forever
+    const visited = new Set<bigint>();
+
+    while (isNonNullLink(currentLink)) {
+        const offset = getLink(currentLink);
+        if (visited.has(offset)) {
+            throw new Error('Circular reference in file history');
+        }
+        visited.add(offset);
+        const fileHistory = await readFileHistoryBlock(currentLink, reader);
+        yield fileHistory;
+        currentLink = fileHistory.fileHistoryNext;
+    }
+}
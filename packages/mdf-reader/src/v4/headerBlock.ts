@@ -2,18 +2,24 @@ import { Link, readBlock, MaybeLinked, GenericBlock, NonNullLink } from './commo
 import { DataGroupBlock, resolveDataGroupOffset } from './dataGroupBlock';
 import { FileHistoryBlock, resolveFileHistoryOffset } from './fileHistoryBlock';
 import { SerializeContext, type SerializeWriteFunction } from './serializer';
+import { TextBlock, MetadataBlock, resolveMetadataOffset } from './textBlock';
 import { BufferedFileReader } from '../bufferedFileReader';
 
+export enum TimeFlags {
+    LocalTime = 0x01,
+    OffsetsValid = 0x02,
+}
+
 export interface Header<TMode extends 'linked' | 'instanced' = 'linked'> {
     firstDataGroup: MaybeLinked<DataGroupBlock<TMode> | null, TMode>;
     fileHistory: MaybeLinked<FileHistoryBlock<TMode> | null, TMode>;
     channelHierarchy: MaybeLinked<unknown, TMode>;
     attachment: MaybeLinked<unknown, TMode>;
     event: MaybeLinked<unknown, TMode>;
-    comment: MaybeLinked<unknown, TMode>;
+    comment: MaybeLinked<TextBlock | MetadataBlock | null, TMode>;
     startTime: bigint; // nanoseconds since unix epoch
-    timeZone: number;
-    dstOffset: number;
+    timeZone: number; // minutes, valid when TimeFlags.OffsetsValid is set
+    dstOffset: number; // minutes, valid when TimeFlags.OffsetsValid is set
     timeFlags: number;
     timeQuality: number;
     flags: number;
@@ -30,10 +36,10 @@ export function deserializeHeader(block: GenericBlock): Header {
         channelHierarchy: block.links[2] as Link<unknown>,
         attachment: block.links[3] as Link<unknown>,
         event: block.links[4] as Link<unknown>,
-        comment: block.links[5] as Link<unknown>,
+        comment: block.links[5] as Link<TextBlock | MetadataBlock>,
         startTime: view.getBigUint64(0, true),
-        timeZone: view.getUint16(8, true),
-        dstOffset: view.getUint16(10, true),
+        timeZone: view.getInt16(8, true),
+        dstOffset: view.getInt16(10, true),
         timeFlags: view.getUint8(12),
         timeQuality: view.getUint8(13),
         flags: view.getUint8(14),
@@ -55,8 +61,8 @@ export async function serializeHeader(write: SerializeWriteFunction, context: Se
             view.setBigUint64(32, context.get(header.event), true);
             view.setBigUint64(40, context.get(header.comment), true);
             view.setBigUint64(48, header.startTime, true);
-            view.setUint16(56, header.timeZone, true);
-            view.setUint16(58, header.dstOffset, true);
+            view.setInt16(56, header.timeZone, true);
+            view.setInt16(58, header.dstOffset, true);
             view.setUint8(60, header.timeFlags);
             view.setUint8(61, header.timeQuality);
             view.setUint8(62, header.flags);
@@ -78,6 +84,7 @@ export function resolveHeaderOffset(context: SerializeContext, header: Header<'i
         block => {
             resolveDataGroupOffset(context, block.firstDataGroup);
             resolveFileHistoryOffset(context, block.fileHistory);
+            resolveMetadataOffset(context, block.comment);
         }
     );
 }
@@ -0,0 +1,14 @@
+// Blocks are written at a non-zero offset, since a zero link means "no block"
+export const testBlockOffset = 8;
+
+// Writes a single block header and its payload at testBlockOffset. The declared length doesn't have to match the
+// payload, so malformed blocks can be built too.
+export function createBlockFile(type: string, length: bigint, linkCount: bigint, payload: Uint8Array = new Uint8Array(0)): File {
+    const bytes = new Uint8Array(testBlockOffset + Math.max(24 + payload.byteLength, Number(length)));
+    const view = new DataView(bytes.buffer);
+    bytes.set(new TextEncoder().encode(type), testBlockOffset);
+    view.setBigUint64(testBlockOffset + 8, length, true);
+    view.setBigUint64(testBlockOffset + 16, linkCount, true);
+    bytes.set(payload, testBlockOffset + 24);
+    return new File([bytes], 'block.mf4');
+}
@@ -0,0 +1,37 @@
+import { describe, it, expect } from 'vitest';
+import { BufferedFileReader } from '../bufferedFileReader';
+import { newNonNullLink } from './common';
+import { getMetadataText, readTextBlock, readTextOrMetadataBlock } from './textBlock';
+import { createBlockFile, testBlockOffset } from './testUtils';
+
+function createTextFile(type: string, text: string): File {
+    const payload = new TextEncoder().encode(text + '\0');
+    return createBlockFile(type, BigInt(24 + payload.byteLength), 0n, payload);
+}
+
+describe('textBlock v4', () => {
+    it('should read text from a text block', async () => {
+        const reader = new BufferedFileReader(createTextFile('##TX', 'km/h'));
+        expect((await readTextOrMetadataBlock(newNonNullLink(BigInt(testBlockOffset)), reader)).data).toBe('km/h');
+    });
+
+    it('should read text from a metadata block', async () => {
+        const unit = '<CNunit><TX>km/h</TX></CNunit>';
+        const reader = new BufferedFileReader(createTextFile('##MD', unit));
+        expect((await readTextOrMetadataBlock(newNonNullLink(BigInt(testBlockOffset)), reader)).data).toBe(unit);
+    });
+
+    it('should reject metadata when only text is expected', async () => {
+        const reader = new BufferedFileReader(createTextFile('##MD', '<CNunit/>'));
+        await expect(readTextBlock(newNonNullLink(BigInt(testBlockOffset)), reader)).rejects.toThrow('Invalid block tag: "##MD"');
+    });
+
+    it('should extract the text element from metadata', () => {
+        expect(getMetadataText({ data: '<CNunit><TX>km/h</TX></CNunit>' })).toBe('km/h');
+        expect(getMetadataText({ data: '<CNunit xmlns="http://www.asam.net/mdf/v4"><TX xml:space="preserve">&#181;m &amp; &lt;s&gt;</TX></CNunit>' })).toBe('µm & <s>');
+    });
+
+    it('should return null for metadata without a text element', () => {
+        expect(getMetadataText({ data: '<CNunit><names/></CNunit>' })).toBeNull();
+    });
+});
@@ -52,6 +52,29 @@ export async function readMetadataBlock(link: Link<MetadataBlock>, reader: Buffe
     return block === null ? null : deserializeMetadataBlock(block);
 }
 
+// Some links may point at either plain text or XML metadata, such as units and comments
+export async function readTextOrMetadataBlock(link: NonNullLink<TextBlock | MetadataBlock>, reader: BufferedFileReader): Promise<TextBlock | MetadataBlock>;
+export async function readTextOrMetadataBlock(link: Link<TextBlock | MetadataBlock>, reader: BufferedFileReader): Promise<TextBlock | MetadataBlock | null>;
+export async function readTextOrMetadataBlock(link: Link<TextBlock | MetadataBlock>, reader: BufferedFileReader): Promise<TextBlock | MetadataBlock | null> {
+    const block = await readBlock(link, reader, ["##TX", "##MD"]);
+    return block === null ? null : deserializeTextBlock(block);
+}
+
+const xmlEntities: Record<string, string> = { amp: '&', lt: '<', gt: '>', quot: '"', apos: "'" };
+
+// Extracts the plain text of the <TX> element from XML metadata, e.g. "km/h" from <CNunit><TX>km/h</TX></CNunit>.
+// Uses a regular expression since DOMParser isn't available in workers.
+export function getMetadataText(block: MetadataBlock): string | null {
+    const match = /<TX(?:\s[^>]*)?(?<!\/)>([\s\S]*?)<\/TX>/.exec(block.data);
+    if (match === null) {
+        return null;
+    }
+    return match[1].replace(/&(?:#x([0-9a-fA-F]+)|#([0-9]+)|(\w+));/g, (entity, hex, decimal, name) =>
+        hex !== undefined ? String.fromCodePoint(parseInt(hex, 16)) :
+        decimal !== undefined ? String.fromCodePoint(parseInt(decimal, 10)) :
+        xmlEntities[name] ?? entity);
+}
+
 function getEncodedLength(data: string): number {
     return new TextEncoder().encode(data).byteLength + 1;
 }
@@ -18,6 +18,7 @@ interface LoadedSignalData {
     dataGroup: MdfDataGroup;
     channel: MdfChannel;
     timeChannel: MdfChannel | undefined;
+    fileSize: number;
 }
 
 // Memory budget for loading all channels in a data group at once. Groups that exceed this are loaded signal-by-signal
@@ -51,11 +52,19 @@ const groupPendingBatch = new Map<MdfDataGroup, PendingBatch>();
 const activeProgressCallbacks = new Map<number, () => void>();
 let signalId = 0;
 
-function createSharedBuffer(numberType: NumberType): SharedBuffer {
+// Upper bound on rows pre-allocated from a channel group's declared row count. Buffers still grow past this if the data
+// really is larger.
+const maxPreallocatedRows = 16 * 1024 * 1024;
+const minPreallocatedRows = 1024;
+
+function createSharedBuffer(numberType: NumberType, rowCount: number, fileSize: number): SharedBuffer {
+    // The declared row count can't be trusted, and every record takes at least one byte, so also limit it to the file
+    // size. This stops a small corrupt or malicious file from forcing a huge allocation up front.
+    const capacity = Math.max(Math.min(rowCount, fileSize, maxPreallocatedRows), minPreallocatedRows);
     switch (numberType) {
-        case NumberType.BigInt64: return new SharedBufferBigInt64Sequence();
-        case NumberType.BigUint64: return new SharedBufferBigUint64Sequence();
-        default: return new SharedBufferFloat64Sequence();
+        case NumberType.BigInt64: return new SharedBufferBigInt64Sequence(capacity);
+        case NumberType.BigUint64: return new SharedBufferBigUint64Sequence(capacity);
+        default: return new SharedBufferFloat64Sequence(capacity);
     }
 }
 
@@ -87,6 +96,7 @@ self.addEventListener('message', async (event: MessageEvent<WorkerMessage>) => {
                             dataGroup,
                             channel,
                             timeChannel,
+                            fileSize: message.file.size,
                         });
 
                         signals.push({
@@ -126,7 +136,7 @@ self.addEventListener('message', async (event: MessageEvent<WorkerMessage>) => {
                 throw new Error(`Signal ${message.signalId} not found`);
             }
 
-            const { mdfFile, dataGroup, channel, timeChannel } = signalData;
+            const { mdfFile, dataGroup, channel, timeChannel, fileSize } = signalData;
 
             const [valuesConversion, timeConversion, timeUnit, valueUnit] = await Promise.all([
                 channel.getConversion(),
@@ -180,7 +190,7 @@ self.addEventListener('message', async (event: MessageEvent<WorkerMessage>) => {
 
                 for (const ch of channelsToLoad) {
                     if (!channelCache.has(ch)) {
-                        const buffer = createSharedBuffer(ch.numberType);
+                        const buffer = createSharedBuffer(ch.numberType, ch.channelGroup.rowCount, fileSize);
                         channelCache.set(ch, { buffer, loading: pending.promise });
                         pending.channels.set(ch, buffer);
                     }